@@ -0,0 +1,220 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+// Alias file, relative to the directory SoulDOS is started from
+pub const ALIAS_FILE: &str = ".souldos_aliases";
+
+// Aliases may refer to other aliases; chains longer than this are refused
+pub const MAX_ALIAS_DEPTH: usize = 8;
+
+pub struct Aliases {
+    definitions: BTreeMap<String, String>,
+    // None keeps the aliases in memory only
+    path: Option<PathBuf>,
+}
+
+impl Aliases {
+    // Loads `name=command` lines from `path`; a missing file starts empty
+    pub fn load(path: PathBuf) -> Self {
+        let mut definitions = BTreeMap::new();
+        if let Ok(contents) = fs::read_to_string(&path) {
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                match parse_definition(line) {
+                    Ok((name, command)) => {
+                        definitions.insert(name, command);
+                    }
+                    Err(e) => println!("Skipping alias entry '{}': {}", line, e),
+                }
+            }
+        }
+        Aliases { definitions, path: Some(path) }
+    }
+
+    // Starts empty and never touches the alias file
+    pub fn in_memory() -> Self {
+        Aliases { definitions: BTreeMap::new(), path: None }
+    }
+
+    // Records `name=command` and saves the alias file straight away
+    pub fn define(&mut self, definition: &str) -> Result<(), String> {
+        let (name, command) = parse_definition(definition)?;
+        self.definitions.insert(name, command);
+        self.save()
+    }
+
+    // Drops an alias and saves the alias file straight away
+    pub fn remove(&mut self, name: &str) -> Result<(), String> {
+        if self.definitions.remove(name).is_none() {
+            return Err(format!("{}: not found", name));
+        }
+        self.save()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.definitions.get(name).map(String::as_str)
+    }
+
+    // Sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.definitions.iter()
+    }
+
+    // Replaces a leading alias name with its command, repeatedly. As in bash, a name
+    // already expanded in this pass is left as is, so `alias ping=ping` runs ping.
+    pub fn expand(&self, input: &str) -> Result<String, String> {
+        let mut expanded = input.trim().to_string();
+        let mut seen = HashSet::new();
+        // One pass more than the limit, to see whether the last expansion is itself an alias
+        for _ in 0..=MAX_ALIAS_DEPTH {
+            let (first, rest) = match expanded.split_once(char::is_whitespace) {
+                Some((first, rest)) => (first, rest.trim_start()),
+                None => (expanded.as_str(), ""),
+            };
+            if !seen.insert(first.to_string()) {
+                return Ok(expanded);
+            }
+            let Some(command) = self.definitions.get(first) else {
+                return Ok(expanded);
+            };
+            expanded = if rest.is_empty() {
+                command.clone()
+            } else {
+                format!("{} {}", command, rest)
+            };
+        }
+        Err(format!("alias: expansion of '{}' exceeded {} levels", input.trim(), MAX_ALIAS_DEPTH))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents: String = self
+            .definitions
+            .iter()
+            .map(|(name, command)| format!("{}={}\n", name, command))
+            .collect();
+        fs::write(path, contents)
+            .map_err(|e| format!("Failed to save aliases to '{}': {}", path.display(), e))
+    }
+}
+
+fn parse_definition(definition: &str) -> Result<(String, String), String> {
+    let (name, command) = definition
+        .split_once('=')
+        .ok_or_else(|| "expected <name>=<command>".to_string())?;
+    let name = name.trim();
+    let command = command.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) || name.starts_with('!') || name.starts_with('#') {
+        return Err(format!("invalid alias name '{}'", name));
+    }
+    if command.is_empty() {
+        return Err(format!("alias '{}' has an empty command", name));
+    }
+    Ok((name.to_string(), command.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempPath;
+
+    // Aliases backed by a temp file that is removed along with the returned guard
+    fn aliases_with(name: &str, definitions: &[&str]) -> (Aliases, TempPath) {
+        let path = TempPath::new(&format!("aliases_{}", name));
+        let mut aliases = Aliases::load(path.to_path_buf());
+        for definition in definitions {
+            aliases.define(definition).unwrap();
+        }
+        (aliases, path)
+    }
+
+    #[test]
+    fn define_saves_and_reloads() {
+        let path = TempPath::new("aliases_reload");
+        let mut aliases = Aliases::load(path.to_path_buf());
+        aliases.define("p=ping").unwrap();
+        aliases.define("c = collapse-truth joy").unwrap();
+
+        let reloaded = Aliases::load(path.to_path_buf());
+        assert_eq!(reloaded.get("p"), Some("ping"));
+        assert_eq!(reloaded.get("c"), Some("collapse-truth joy"));
+    }
+
+    #[test]
+    fn define_rejects_malformed_definitions() {
+        let (mut aliases, _path) = aliases_with("malformed", &[]);
+        assert!(aliases.define("ping").is_err());
+        assert!(aliases.define("=ping").is_err());
+        assert!(aliases.define("p=").is_err());
+        assert!(aliases.define("!1=ping").is_err());
+    }
+
+    #[test]
+    fn load_skips_blank_lines() {
+        let path = TempPath::new("aliases_blank_lines");
+        fs::write(&path, "\np=ping\n\n  \n").unwrap();
+        let aliases = Aliases::load(path.to_path_buf());
+        assert_eq!(aliases.iter().count(), 1);
+    }
+
+    #[test]
+    fn expand_replaces_leading_alias_and_keeps_arguments() {
+        let (aliases, _path) = aliases_with("expand", &["c=collapse-truth joy", "cc=c calm"]);
+        assert_eq!(aliases.expand("c calm now"), Ok("collapse-truth joy calm now".to_string()));
+        assert_eq!(aliases.expand("cc now"), Ok("collapse-truth joy calm now".to_string()));
+        assert_eq!(aliases.expand("ping c"), Ok("ping c".to_string()));
+    }
+
+    #[test]
+    fn self_referential_alias_expands_once() {
+        let (aliases, _path) = aliases_with("self_ref", &["ping=ping", "status=status --verbose"]);
+        assert_eq!(aliases.expand("ping"), Ok("ping".to_string()));
+        assert_eq!(aliases.expand("status"), Ok("status --verbose".to_string()));
+    }
+
+    #[test]
+    fn alias_loop_stops_at_repeated_name() {
+        let (aliases, _path) = aliases_with("loop", &["a=b", "b=a"]);
+        assert_eq!(aliases.expand("a"), Ok("a".to_string()));
+    }
+
+    // a0=a1, a1=a2, ... with `levels` aliases; the last target is not an alias
+    fn alias_chain(name: &str, levels: usize) -> (Aliases, TempPath) {
+        let definitions: Vec<String> = (0..levels).map(|i| format!("a{}=a{}", i, i + 1)).collect();
+        let definitions: Vec<&str> = definitions.iter().map(String::as_str).collect();
+        aliases_with(name, &definitions)
+    }
+
+    #[test]
+    fn alias_chain_at_max_depth_expands() {
+        let (aliases, _path) = alias_chain("chain_max", MAX_ALIAS_DEPTH);
+        assert_eq!(aliases.expand("a0"), Ok(format!("a{}", MAX_ALIAS_DEPTH)));
+    }
+
+    #[test]
+    fn overlong_alias_chain_is_refused() {
+        let (aliases, _path) = alias_chain("chain_over", MAX_ALIAS_DEPTH + 1);
+        assert!(aliases.expand("a0").is_err());
+    }
+
+    #[test]
+    fn in_memory_aliases_define_and_remove_without_a_file() {
+        let mut aliases = Aliases::in_memory();
+        aliases.define("p=ping").unwrap();
+        assert_eq!(aliases.expand("p"), Ok("ping".to_string()));
+        aliases.remove("p").unwrap();
+        assert_eq!(aliases.get("p"), None);
+    }
+
+    #[test]
+    fn remove_drops_alias_and_saves() {
+        let path = TempPath::new("aliases_remove");
+        let mut aliases = Aliases::load(path.to_path_buf());
+        aliases.define("p=ping").unwrap();
+        aliases.remove("p").unwrap();
+        assert!(aliases.remove("p").is_err());
+        assert_eq!(Aliases::load(path.to_path_buf()).get("p"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "line-editor")]
+use rustyline::completion::Completer;
+#[cfg(feature = "line-editor")]
+use rustyline::highlight::Highlighter;
+#[cfg(feature = "line-editor")]
+use rustyline::hint::Hinter;
+#[cfg(feature = "line-editor")]
+use rustyline::validate::Validator;
+#[cfg(feature = "line-editor")]
+use rustyline::{Context, Helper};
+
+// Tab completion for the SoulDOS prompt: command names for the first word,
+// file system paths for every word after it. Needs no terminal; the rustyline
+// traits below (feature "line-editor") only forward to candidates().
+#[derive(Default)]
+pub struct CommandCompleter {
+    commands: Vec<String>,
+}
+
+impl CommandCompleter {
+    pub fn set_commands(&mut self, commands: &[String]) {
+        self.commands = commands.to_vec();
+        self.commands.sort();
+        self.commands.dedup();
+    }
+
+    // Returns where the word under the cursor starts and the candidates that could replace it
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before_cursor = &line[..pos];
+        // Step past the separator by its UTF-8 width; NBSP and ideographic spaces are multi-byte
+        let word_start = before_cursor
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(0);
+        let word = &before_cursor[word_start..];
+
+        if before_cursor[..word_start].trim().is_empty() {
+            (word_start, self.complete_command(word))
+        } else {
+            (word_start, complete_path(word))
+        }
+    }
+
+    fn complete_command(&self, prefix: &str) -> Vec<String> {
+        self.commands
+            .iter()
+            .filter(|command| command.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+// Lists entries of the prefix's directory whose names continue the prefix.
+// Directories get a trailing '/'; dotfiles only show when the prefix asks for them.
+fn complete_path(prefix: &str) -> Vec<String> {
+    let (dir_part, name_prefix) = match prefix.rfind('/') {
+        Some(i) => prefix.split_at(i + 1),
+        None => ("", prefix),
+    };
+    let dir = if dir_part.is_empty() { Path::new(".") } else { Path::new(dir_part) };
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(name_prefix) || (name.starts_with('.') && !name_prefix.starts_with('.')) {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir_part, name, suffix))
+        })
+        .collect();
+    matches.sort();
+    matches
+}
+
+#[cfg(feature = "line-editor")]
+impl Completer for CommandCompleter {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+#[cfg(feature = "line-editor")]
+impl Hinter for CommandCompleter {
+    type Hint = String;
+}
+
+#[cfg(feature = "line-editor")]
+impl Highlighter for CommandCompleter {}
+
+#[cfg(feature = "line-editor")]
+impl Validator for CommandCompleter {}
+
+#[cfg(feature = "line-editor")]
+impl Helper for CommandCompleter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempPath;
+
+    fn completer(commands: &[&str]) -> CommandCompleter {
+        let mut completer = CommandCompleter::default();
+        completer.set_commands(&commands.iter().map(|c| c.to_string()).collect::<Vec<_>>());
+        completer
+    }
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    // Directory with a file, a subdirectory and a dotfile, removed with the guard
+    fn sample_dir(name: &str) -> TempPath {
+        let dir = TempPath::new(name);
+        fs::create_dir_all(dir.join("modules")).unwrap();
+        fs::write(dir.join("model.onnx"), b"").unwrap();
+        fs::write(dir.join(".hidden"), b"").unwrap();
+        dir
+    }
+
+    #[test]
+    fn first_word_completes_command_names_by_prefix() {
+        let completer = completer(&["celestial_list_clouds", "celestial_add_cloud", "check-module-integrity", "ping"]);
+        assert_eq!(
+            completer.candidates("celes", 5),
+            (0, strings(&["celestial_add_cloud", "celestial_list_clouds"]))
+        );
+        assert_eq!(completer.candidates("p", 1), (0, strings(&["ping"])));
+        assert_eq!(completer.candidates("zz", 2), (0, Vec::new()));
+    }
+
+    #[test]
+    fn later_words_complete_paths() {
+        let dir = sample_dir("completion_paths");
+        let prefix = format!("{}/mo", dir.display());
+        let line = format!("run-onnx-test {}", prefix);
+
+        let (start, candidates) = completer(&[]).candidates(&line, line.len());
+        assert_eq!(start, "run-onnx-test ".len());
+        assert_eq!(
+            candidates,
+            vec![format!("{}/model.onnx", dir.display()), format!("{}/modules/", dir.display())]
+        );
+    }
+
+    #[test]
+    fn dotfiles_only_complete_for_dot_prefix() {
+        let dir = sample_dir("completion_dotfiles");
+        let all = format!("source {}/", dir.display());
+        let dotted = format!("source {}/.", dir.display());
+
+        let (_, candidates) = completer(&[]).candidates(&all, all.len());
+        assert!(!candidates.iter().any(|c| c.ends_with(".hidden")));
+        assert_eq!(candidates.len(), 2);
+
+        let (_, candidates) = completer(&[]).candidates(&dotted, dotted.len());
+        assert_eq!(candidates, vec![format!("{}/.hidden", dir.display())]);
+    }
+
+    #[test]
+    fn multi_byte_whitespace_does_not_split_a_character() {
+        let completer = completer(&["source", "status"]);
+
+        // U+00A0 (Option+Space on macOS) between command and argument
+        let dir = sample_dir("completion_nbsp");
+        let line = format!("source\u{a0}{}/mode", dir.display());
+        assert_eq!(
+            completer.candidates(&line, line.len()),
+            ("source\u{a0}".len(), vec![format!("{}/model.onnx", dir.display())])
+        );
+
+        // U+3000 ideographic space before the first word
+        let line = "\u{3000}st";
+        assert_eq!(completer.candidates(line, line.len()), ("\u{3000}".len(), strings(&["status"])));
+    }
+}
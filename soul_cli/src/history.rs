@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+// History file, relative to the directory SoulDOS is started from
+pub const HISTORY_FILE: &str = ".souldos_history";
+
+// Oldest entries are dropped beyond this so the file doesn't grow forever
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+pub struct History {
+    entries: Vec<String>,
+    path: PathBuf,
+}
+
+impl History {
+    // Loads previous sessions' history from `path`; a missing file starts empty
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match fs::read_to_string(&path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.to_string())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let mut history = History { entries, path };
+        history.truncate();
+        history
+    }
+
+    pub fn push(&mut self, command: &str) {
+        self.entries.push(command.to_string());
+        self.truncate();
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    // Nothing is written while there are no entries
+    pub fn save(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let mut contents = self.entries.join("\n");
+        contents.push('\n');
+        fs::write(&self.path, contents)
+            .map_err(|e| format!("Failed to save history to '{}': {}", self.path.display(), e))
+    }
+
+    // Expands a `!N` reference to the Nth (1-based) entry; other input is returned unchanged
+    pub fn expand(&self, input: &str) -> Result<String, String> {
+        let Some(reference) = input.strip_prefix('!') else {
+            return Ok(input.to_string());
+        };
+        reference
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|index| self.entries.get(index))
+            .cloned()
+            .ok_or_else(|| format!("{}: event not found", input))
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > MAX_HISTORY_ENTRIES {
+            let excess = self.entries.len() - MAX_HISTORY_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempPath;
+
+    // Never saved, so the path only has to not exist
+    fn history_with(entries: &[&str]) -> History {
+        let mut history = History::load(TempPath::new("history_unsaved").to_path_buf());
+        for entry in entries {
+            history.push(entry);
+        }
+        history
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = TempPath::new("history_round_trip");
+        let mut history = History::load(path.to_path_buf());
+        history.push("ping");
+        history.push("collapse-truth joy calm now");
+        history.save().unwrap();
+
+        let reloaded = History::load(path.to_path_buf());
+        assert_eq!(reloaded.entries(), ["ping", "collapse-truth joy calm now"]);
+    }
+
+    #[test]
+    fn empty_history_is_not_written() {
+        let path = TempPath::new("history_empty");
+        History::load(path.to_path_buf()).save().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn expand_resolves_one_based_references() {
+        let history = history_with(&["ping", "ver"]);
+        assert_eq!(history.expand("!1"), Ok("ping".to_string()));
+        assert_eq!(history.expand("!2"), Ok("ver".to_string()));
+        assert_eq!(history.expand("status"), Ok("status".to_string()));
+    }
+
+    #[test]
+    fn expand_rejects_missing_or_malformed_references() {
+        let history = history_with(&["ping"]);
+        assert_eq!(history.expand("!0"), Err("!0: event not found".to_string()));
+        assert_eq!(history.expand("!99"), Err("!99: event not found".to_string()));
+        assert_eq!(history.expand("!abc"), Err("!abc: event not found".to_string()));
+    }
+}
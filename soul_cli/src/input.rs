@@ -0,0 +1,336 @@
+use std::io::{self, Write};
+
+// Consecutive read failures tolerated before the prompt loop gives up
+pub const MAX_CONSECUTIVE_READ_ERRORS: u32 = 3;
+
+// Prompt shown while a backslash-continued command is being collected
+pub const CONTINUATION_PROMPT: &str = "...> ";
+
+// What a single read from the prompt produced
+#[derive(Debug, PartialEq)]
+pub enum LineEvent {
+    Line(String),
+    // Ctrl-C: whatever is being typed, including continuation lines, is dropped.
+    // Only the line editor reports this; on plain stdin Ctrl-C ends the process.
+    #[cfg_attr(not(feature = "line-editor"), allow(dead_code))]
+    Interrupted,
+    // Input is exhausted (Ctrl-D or closed pipe)
+    Eof,
+}
+
+// Source of lines for the SoulDOS prompt loop
+pub trait LineReader {
+    fn read_line(&mut self, prompt: &str) -> io::Result<LineEvent>;
+
+    // Makes an earlier command available for recall, where the reader supports it
+    fn add_history(&mut self, _entry: &str) {}
+
+    // Command names offered for tab completion, where the reader supports it
+    fn set_command_names(&mut self, _commands: &[String]) {}
+}
+
+// Plain stdin reader, used for piped input or when the line editor is unavailable
+pub struct StdinReader;
+
+impl LineReader for StdinReader {
+    fn read_line(&mut self, prompt: &str) -> io::Result<LineEvent> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(LineEvent::Eof);
+        }
+        Ok(LineEvent::Line(input))
+    }
+}
+
+// Line editor with cursor movement and arrow-key history (feature "line-editor")
+#[cfg(feature = "line-editor")]
+pub struct EditorReader {
+    editor: rustyline::Editor<crate::completion::CommandCompleter, rustyline::history::DefaultHistory>,
+}
+
+#[cfg(feature = "line-editor")]
+impl EditorReader {
+    pub fn new() -> Result<Self, String> {
+        let mut editor = rustyline::Editor::new().map_err(|e| e.to_string())?;
+        editor.set_helper(Some(crate::completion::CommandCompleter::default()));
+        Ok(EditorReader { editor })
+    }
+}
+
+#[cfg(feature = "line-editor")]
+impl LineReader for EditorReader {
+    fn read_line(&mut self, prompt: &str) -> io::Result<LineEvent> {
+        use rustyline::error::ReadlineError;
+
+        match self.editor.readline(prompt) {
+            // Recall entries come from the prompt loop via add_history, not from raw lines
+            Ok(line) => Ok(LineEvent::Line(line)),
+            Err(ReadlineError::Interrupted) => Ok(LineEvent::Interrupted),
+            Err(ReadlineError::Eof) => Ok(LineEvent::Eof),
+            Err(ReadlineError::Io(e)) => Err(e),
+            Err(e) => Err(io::Error::other(e.to_string())),
+        }
+    }
+
+    fn add_history(&mut self, entry: &str) {
+        // History is in-memory only; a failure here just means no recall for this entry
+        let _ = self.editor.add_history_entry(entry);
+    }
+
+    fn set_command_names(&mut self, commands: &[String]) {
+        if let Some(completer) = self.editor.helper_mut() {
+            completer.set_commands(commands);
+        }
+    }
+}
+
+// Reads the next line, retrying transient failures. Err means input is unusable
+// (e.g. a broken pipe) and the caller should stop with a non-zero exit.
+pub fn read_line_with_retry(reader: &mut dyn LineReader, prompt: &str) -> Result<LineEvent, String> {
+    let mut consecutive_errors = 0;
+    loop {
+        match reader.read_line(prompt) {
+            Ok(line) => return Ok(line),
+            Err(e) => {
+                consecutive_errors += 1;
+                println!("Error reading input: {}", e);
+                if consecutive_errors >= MAX_CONSECUTIVE_READ_ERRORS {
+                    return Err(format!("Input failed {} times in a row, shutting down.", consecutive_errors));
+                }
+            }
+        }
+    }
+}
+
+// Reads one command, following lines that end in a backslash onto the next line.
+// Ok(None) means input is exhausted; an interrupted command comes back as an empty line.
+pub fn read_command(reader: &mut dyn LineReader, prompt: &str) -> Result<Option<String>, String> {
+    let mut lines = Vec::new();
+    let mut current_prompt = prompt;
+    loop {
+        match read_line_with_retry(reader, current_prompt)? {
+            LineEvent::Line(line) => {
+                // Comments are dropped before joining: one ending in '\' continues
+                // nothing, and one inside a continued command is left out of it
+                if is_comment(&line) {
+                    if lines.is_empty() {
+                        return Ok(Some(line));
+                    }
+                    continue;
+                }
+                let continues = strip_continuation(&line).is_some();
+                lines.push(line);
+                if !continues {
+                    break;
+                }
+                current_prompt = CONTINUATION_PROMPT;
+            }
+            LineEvent::Interrupted => return Ok(Some(String::new())),
+            LineEvent::Eof => break,
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok(None);
+    }
+    // Input ending mid-continuation still yields whatever was collected
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    Ok(join_continuations(&lines).pop())
+}
+
+// Joins each line ending in a backslash with the line after it. The backslash is
+// replaced by a space so the last word of one line never fuses with the next.
+pub fn join_continuations(lines: &[&str]) -> Vec<String> {
+    let mut joined = Vec::new();
+    let mut pending = String::new();
+    for line in lines {
+        match strip_continuation(line) {
+            Some(head) => {
+                pending.push_str(head);
+                pending.push(' ');
+            }
+            None => {
+                pending.push_str(line);
+                joined.push(std::mem::take(&mut pending));
+            }
+        }
+    }
+    if !pending.is_empty() {
+        joined.push(pending);
+    }
+    joined
+}
+
+fn strip_continuation(line: &str) -> Option<&str> {
+    line.trim_end().strip_suffix('\\')
+}
+
+// A line whose first non-whitespace char is '#'. A '#' later in the line is left
+// alone for the command to see.
+pub fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+// Blank lines and comments are skipped
+pub fn is_blank_or_comment(line: &str) -> bool {
+    line.trim().is_empty() || is_comment(line)
+}
+
+// Picks the line editor for interactive terminals, plain stdin otherwise
+pub fn new_line_reader() -> Box<dyn LineReader> {
+    #[cfg(feature = "line-editor")]
+    {
+        use std::io::IsTerminal;
+        if io::stdin().is_terminal() {
+            match EditorReader::new() {
+                Ok(reader) => return Box::new(reader),
+                Err(e) => println!("Line editor unavailable ({}), falling back to plain input.", e),
+            }
+        }
+    }
+    Box::new(StdinReader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    // Replays canned read results and counts how often it was asked
+    struct ScriptedReader {
+        results: VecDeque<io::Result<LineEvent>>,
+        calls: usize,
+    }
+
+    impl ScriptedReader {
+        fn new(results: Vec<io::Result<LineEvent>>) -> Self {
+            ScriptedReader { results: results.into(), calls: 0 }
+        }
+    }
+
+    impl LineReader for ScriptedReader {
+        fn read_line(&mut self, _prompt: &str) -> io::Result<LineEvent> {
+            self.calls += 1;
+            self.results.pop_front().unwrap_or(Ok(LineEvent::Eof))
+        }
+    }
+
+    // Fails every read, like stdin on a broken pipe
+    struct FailingReader {
+        calls: u32,
+    }
+
+    impl LineReader for FailingReader {
+        fn read_line(&mut self, _prompt: &str) -> io::Result<LineEvent> {
+            self.calls += 1;
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken pipe"))
+        }
+    }
+
+    fn read_error() -> io::Result<LineEvent> {
+        Err(io::Error::other("read failed"))
+    }
+
+    fn line(text: &str) -> io::Result<LineEvent> {
+        Ok(LineEvent::Line(text.to_string()))
+    }
+
+    #[test]
+    fn read_line_with_retry_gives_up_after_max_consecutive_errors() {
+        let mut reader = FailingReader { calls: 0 };
+        assert!(read_line_with_retry(&mut reader, "> ").is_err());
+        assert_eq!(reader.calls, MAX_CONSECUTIVE_READ_ERRORS);
+    }
+
+    #[test]
+    fn read_line_with_retry_resets_count_after_a_successful_read() {
+        let mut results = Vec::new();
+        for _ in 1..MAX_CONSECUTIVE_READ_ERRORS {
+            results.push(read_error());
+        }
+        results.push(line("ping\n"));
+        for _ in 1..MAX_CONSECUTIVE_READ_ERRORS {
+            results.push(read_error());
+        }
+        results.push(line("ver\n"));
+        let mut reader = ScriptedReader::new(results);
+
+        assert_eq!(read_line_with_retry(&mut reader, "> "), Ok(LineEvent::Line("ping\n".to_string())));
+        assert_eq!(read_line_with_retry(&mut reader, "> "), Ok(LineEvent::Line("ver\n".to_string())));
+        assert_eq!(reader.calls, 2 * MAX_CONSECUTIVE_READ_ERRORS as usize);
+    }
+
+    #[test]
+    fn join_continuations_replaces_trailing_backslash_with_a_space() {
+        assert_eq!(join_continuations(&["a \\", "b"]), vec!["a  b".to_string()]);
+        assert_eq!(join_continuations(&["a\\", "b"]), vec!["a b".to_string()]);
+    }
+
+    #[test]
+    fn join_continuations_leaves_normal_lines_untouched() {
+        assert_eq!(join_continuations(&["ping", "ver"]), vec!["ping".to_string(), "ver".to_string()]);
+    }
+
+    #[test]
+    fn read_command_joins_continued_lines() {
+        let mut reader = ScriptedReader::new(vec![line("collapse-truth joy \\\n"), line("calm now\n")]);
+        let command = read_command(&mut reader, "> ").unwrap().unwrap();
+        assert_eq!(command.split_whitespace().collect::<Vec<_>>(), ["collapse-truth", "joy", "calm", "now"]);
+    }
+
+    #[test]
+    fn read_command_runs_collected_text_on_eof_mid_continuation() {
+        let mut reader = ScriptedReader::new(vec![line("ver \\\n")]);
+        assert_eq!(read_command(&mut reader, "> ").unwrap().as_deref().map(str::trim), Some("ver"));
+        assert_eq!(read_command(&mut reader, "> "), Ok(None));
+    }
+
+    #[test]
+    fn read_command_discards_pending_continuation_on_interrupt() {
+        let mut reader = ScriptedReader::new(vec![line("ver \\\n"), Ok(LineEvent::Interrupted), line("ping\n")]);
+        assert_eq!(read_command(&mut reader, "> "), Ok(Some(String::new())));
+        assert_eq!(read_command(&mut reader, "> "), Ok(Some("ping\n".to_string())));
+    }
+
+    #[test]
+    fn read_command_does_not_continue_a_comment_line() {
+        let mut reader = ScriptedReader::new(vec![line("# note \\\n"), line("ping\n")]);
+        let comment = read_command(&mut reader, "> ").unwrap().unwrap();
+        assert!(is_blank_or_comment(&comment));
+        assert_eq!(read_command(&mut reader, "> "), Ok(Some("ping\n".to_string())));
+    }
+
+    #[test]
+    fn read_command_leaves_comments_out_of_a_continued_command() {
+        let mut reader = ScriptedReader::new(vec![line("collapse-truth joy \\\n"), line("  # why \\\n"), line("calm now\n")]);
+        let command = read_command(&mut reader, "> ").unwrap().unwrap();
+        assert_eq!(command.split_whitespace().collect::<Vec<_>>(), ["collapse-truth", "joy", "calm", "now"]);
+    }
+
+    #[test]
+    fn leading_hash_lines_are_comments() {
+        assert!(is_blank_or_comment("#foo"));
+        assert!(is_blank_or_comment("   # x"));
+    }
+
+    #[test]
+    fn mid_line_hash_is_kept_and_blank_lines_are_skipped() {
+        assert!(!is_blank_or_comment("echo #hi"));
+        assert!(is_blank_or_comment(""));
+        assert!(is_blank_or_comment("  \t\n"));
+    }
+
+    #[cfg(feature = "line-editor")]
+    #[test]
+    fn editor_reader_hands_command_names_to_completer() {
+        let mut reader = EditorReader::new().expect("editor should build without a terminal");
+        reader.set_command_names(&["status".to_string(), "ping".to_string()]);
+
+        let completer = reader.editor.helper().expect("completer helper should be installed");
+        assert_eq!(completer.candidates("pi", 2), (0, vec!["ping".to_string()]));
+        assert_eq!(completer.candidates("st", 2), (0, vec!["status".to_string()]));
+    }
+}
@@ -1,11 +1,28 @@
 use human_panic::setup_panic;
+use std::fs;
 use std::io::{self, Write};
+use std::path::PathBuf;
 use chrono::Local;
 use clap::{Parser, CommandFactory};
 
 mod hal;
 use hal::{HalTrait, MockHal, TensorData};
 
+mod input;
+
+// Only the line editor completes at the prompt; plain builds still compile and test it
+#[cfg_attr(not(feature = "line-editor"), allow(dead_code))]
+mod completion;
+
+mod history;
+use history::History;
+
+mod alias;
+use alias::Aliases;
+
+#[cfg(test)]
+mod test_support;
+
 #[derive(Parser)]
 #[command(name = "SoulDOS", version = "0.0.1-alpha", about = "CLI for SoulWare OS", help_template = "{about}\nVersion: {version}\n\nUsage: {usage}\n\nCommands:\n{subcommands}")]
 struct Cli {
@@ -13,6 +30,16 @@ struct Cli {
     command: Option<Commands>,
 }
 
+// Flags given when launching the binary, separate from the per-line SoulDOS commands
+#[derive(Parser)]
+#[command(name = "soul_cli", version = "0.0.1-alpha", about = "SoulWare CLI (SoulDOS)")]
+struct LaunchArgs {
+    /// Runs the commands in this file after boot, then exits instead of prompting.
+    /// Saved aliases are not loaded, and aliases the script defines are not saved.
+    #[arg(long)]
+    script: Option<String>,
+}
+
 #[derive(clap::Subcommand)]
 enum Commands {
     /// Displays version information
@@ -47,16 +74,71 @@ enum Commands {
     CollapseTruth { emotion: String, mode: String, time: String },
     /// Runs a test ONNX model via HAL
     RunOnnxTest { model_path: String, input_info: String },
+    /// Lists previously entered commands (re-run one with !N)
+    History,
+    /// Defines (name=command), shows (name) or lists command aliases
+    Alias {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        definition: Vec<String>,
+    },
+    /// Removes a command alias
+    Unalias { name: String },
+    /// Runs the commands in a script file (blank and '#' lines are skipped)
+    Source { script_path: String },
+}
+
+// Scripts may source other scripts; this stops one that sources itself
+const MAX_SCRIPT_DEPTH: usize = 16;
+
+// Shell state carried from one command to the next
+struct Session {
+    history: History,
+    aliases: Aliases,
+    script_depth: usize,
 }
 
-fn handle_command(command: Commands, hal: &impl HalTrait) {
+impl Session {
+    // Picks up history and aliases saved by earlier sessions
+    fn load() -> Self {
+        Session::from_files(history::HISTORY_FILE.into(), alias::ALIAS_FILE.into())
+    }
+
+    fn from_files(history_path: PathBuf, alias_path: PathBuf) -> Self {
+        Session {
+            history: History::load(history_path),
+            aliases: Aliases::load(alias_path),
+            script_depth: 0,
+        }
+    }
+
+    // For --script: aliases saved in the current directory would change what the
+    // script does, so it starts without them and keeps its own to itself
+    fn for_script(history_path: PathBuf) -> Self {
+        Session {
+            history: History::load(history_path),
+            aliases: Aliases::in_memory(),
+            script_depth: 0,
+        }
+    }
+}
+
+// What the caller should do after a command line has been dispatched
+#[derive(Debug, PartialEq)]
+enum DispatchResult {
+    Continue,
+    Exit,
+}
+
+fn handle_command(command: Commands, hal: &impl HalTrait, session: &mut Session) {
     match command {
         Commands::Ver => println!("SoulWare CLI Version 0.0.1 (Alpha)"),
-        Commands::Date => println!("{}", Local::now().format("%Y-%m-%d").to_string()),
-        Commands::Time => println!("{}", Local::now().format("%H:%M:%S").to_string()),
+        Commands::Date => println!("{}", Local::now().format("%Y-%m-%d")),
+        Commands::Time => println!("{}", Local::now().format("%H:%M:%S")),
         Commands::Cls | Commands::Clear => {
             print!("\x1B[2J\x1B[H");
-            io::stdout().flush().unwrap();
+            if let Err(e) = io::stdout().flush() {
+                println!("Error clearing screen: {}", e);
+            }
         }
         Commands::Ls | Commands::Dir => println!("Placeholder: Listing directory contents or module status..."),
         Commands::Status | Commands::Mem => {
@@ -135,9 +217,103 @@ fn handle_command(command: Commands, hal: &impl HalTrait) {
                 Err(e) => println!("Error running ONNX model: {}", e),
             }
         }
+        Commands::History => {
+            for (i, entry) in session.history.entries().iter().enumerate() {
+                println!("{:>5}  {}", i + 1, entry);
+            }
+        }
+        Commands::Alias { definition } => {
+            let definition = definition.join(" ");
+            if definition.is_empty() {
+                for (name, command) in session.aliases.iter() {
+                    println!("{}={}", name, command);
+                }
+            } else if definition.contains('=') {
+                if let Err(e) = session.aliases.define(&definition) {
+                    println!("alias: {}", e);
+                }
+            } else {
+                match session.aliases.get(&definition) {
+                    Some(command) => println!("{}={}", definition, command),
+                    None => println!("alias: {}: not found", definition),
+                }
+            }
+        }
+        Commands::Unalias { name } => {
+            if let Err(e) = session.aliases.remove(&name) {
+                println!("unalias: {}", e);
+            }
+        }
+        Commands::Source { script_path } => {
+            if let Err(e) = run_script(&script_path, hal, session) {
+                println!("{}", e);
+            }
+        }
     }
 }
 
+// Runs one command line, resolving aliases first. Errors are reported, never propagated.
+fn dispatch_line(line: &str, hal: &impl HalTrait, session: &mut Session) -> DispatchResult {
+    let command_line = match session.aliases.expand(line) {
+        Ok(command_line) => command_line,
+        Err(e) => {
+            println!("{}", e);
+            return DispatchResult::Continue;
+        }
+    };
+    let trimmed_input = command_line.trim();
+
+    if trimmed_input == "exit" || trimmed_input == "quit" {
+        return DispatchResult::Exit;
+    }
+
+    // Prepend "souldos" for clap parsing, as it expects the binary name as the first arg
+    let args_for_clap = std::iter::once("souldos").chain(trimmed_input.split_whitespace());
+
+    match Cli::try_parse_from(args_for_clap) {
+        Ok(cli) => {
+            if let Some(command) = cli.command {
+                handle_command(command, hal, session); // Pass HAL instance
+            } else {
+                // Show help if no subcommand is provided
+                if let Err(e) = Cli::command().print_help() {
+                    println!("Error printing help: {}", e);
+                }
+            }
+        }
+        Err(e) => {
+            println!("{}", e);
+        }
+    }
+    DispatchResult::Continue
+}
+
+// Runs each command in a script as if typed. An `exit` in the script ends the script only.
+fn run_script(script_path: &str, hal: &impl HalTrait, session: &mut Session) -> Result<(), String> {
+    if session.script_depth >= MAX_SCRIPT_DEPTH {
+        return Err(format!("source: scripts nested more than {} deep, not running '{}'", MAX_SCRIPT_DEPTH, script_path));
+    }
+    let contents = fs::read_to_string(script_path)
+        .map_err(|e| format!("Failed to read script '{}': {}", script_path, e))?;
+    // Comments go before joining, so a comment ending in '\' can't swallow the next command
+    let lines: Vec<&str> = contents
+        .lines()
+        .filter(|line| !input::is_comment(line))
+        .collect();
+
+    session.script_depth += 1;
+    for command in input::join_continuations(&lines) {
+        if input::is_blank_or_comment(&command) {
+            continue;
+        }
+        if dispatch_line(command.trim(), hal, session) == DispatchResult::Exit {
+            break;
+        }
+    }
+    session.script_depth -= 1;
+    Ok(())
+}
+
 // Renamed to avoid conflict with the one used at boot, or could be merged if identical.
 // For now, a distinct one for clarity within command handling.
 fn print_module_integrity_status_for_command(hal: &impl HalTrait, module_name: &str, signature_source: &str) {
@@ -162,10 +338,30 @@ fn print_module_integrity_status(hal: &impl HalTrait, module_name: &str, manifes
     }
 }
 
+// human-panic 1.x still names the deprecated std::panic::PanicInfo inside its macro
+#[allow(deprecated)]
 fn main() {
     setup_panic!();
+    let launch_args = LaunchArgs::parse();
     let hal = MockHal::new(); // Create HAL instance using the new constructor
 
+    boot(&hal);
+
+    let result = match launch_args.script {
+        Some(script_path) => {
+            let mut session = Session::for_script(history::HISTORY_FILE.into());
+            run_script(&script_path, &hal, &mut session)
+        }
+        None => run_prompt(&hal, &mut Session::load()),
+    };
+    if let Err(e) = result {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+// Banner, initial integrity check, status and NPU bring-up
+fn boot(hal: &impl HalTrait) {
     // Welcome Banner
     println!("***************************************************");
     println!("*                                                 *");
@@ -177,9 +373,9 @@ fn main() {
 
     // Initial System Integrity Check
     println!("\nPerforming initial system integrity check...");
-    print_module_integrity_status(&hal, "SoulOS_Core", "InternalManifest");
-    print_module_integrity_status(&hal, "TensorMemoryDriver", "InternalManifest");
-    print_module_integrity_status(&hal, "RustHAL_Interface", "InternalManifest");
+    print_module_integrity_status(hal, "SoulOS_Core", "InternalManifest");
+    print_module_integrity_status(hal, "TensorMemoryDriver", "InternalManifest");
+    print_module_integrity_status(hal, "RustHAL_Interface", "InternalManifest");
 
     // Initial System Status
     println!("\nFetching initial system status...");
@@ -197,39 +393,171 @@ fn main() {
 
     // Final Ready Message
     println!("\nSystem Initialized. Type 'help' for available commands.");
+}
+
+// Interactive loop. Err means input became unreadable and the process should exit non-zero.
+fn run_prompt(hal: &impl HalTrait, session: &mut Session) -> Result<(), String> {
+    let mut reader = input::new_line_reader();
+    reader.set_command_names(&command_names(&session.aliases));
+    for entry in session.history.entries() {
+        reader.add_history(entry);
+    }
 
     loop {
-        print!("\nSoulDOS> ");
-        io::stdout().flush().unwrap();
+        println!();
+        let input = match input::read_command(reader.as_mut(), "SoulDOS> ") {
+            Ok(Some(line)) => line,
+            Ok(None) => break, // End of input (Ctrl-D or closed pipe)
+            Err(e) => {
+                save_history(&session.history);
+                return Err(e);
+            }
+        };
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
+        if input::is_blank_or_comment(&input) {
+            continue;
+        }
 
-        let trimmed_input = input.trim();
+        // Resolve !N before anything else so recalled commands behave as if typed
+        let expanded_input = match session.history.expand(input.trim()) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                println!("{}", e);
+                continue;
+            }
+        };
+        if expanded_input != input.trim() {
+            println!("{}", expanded_input);
+        }
 
-        if trimmed_input.is_empty() {
-            continue;
+        // History keeps what was typed; aliases are resolved fresh each time it runs
+        if expanded_input != "exit" && expanded_input != "quit" {
+            session.history.push(&expanded_input);
+            reader.add_history(&expanded_input);
         }
 
-        if trimmed_input == "exit" || trimmed_input == "quit" {
+        if dispatch_line(&expanded_input, hal, session) == DispatchResult::Exit {
             break;
         }
+        // The command may have been alias or unalias
+        reader.set_command_names(&command_names(&session.aliases));
+    }
 
-        // Prepend "souldos" for clap parsing, as it expects the binary name as the first arg
-        let args_for_clap = std::iter::once("souldos").chain(trimmed_input.split_whitespace());
-        
-        match Cli::try_parse_from(args_for_clap) {
-            Ok(cli) => {
-                if let Some(command) = cli.command {
-                    handle_command(command, &hal); // Pass HAL instance
-                } else {
-                    // Show help if no subcommand is provided
-                    Cli::command().print_help().unwrap();
-                }
-            }
-            Err(e) => {
-                println!("{}", e.to_string());
-            }
+    save_history(&session.history);
+    Ok(())
+}
+
+// Everything that can be typed as the first word at the prompt, aliases included
+fn command_names(aliases: &Aliases) -> Vec<String> {
+    let mut names: Vec<String> = Cli::command()
+        .get_subcommands()
+        .map(|command| command.get_name().to_string())
+        .collect();
+    names.extend(["help", "exit", "quit"].map(String::from));
+    names.extend(aliases.iter().map(|(name, _)| name.clone()));
+    names
+}
+
+fn save_history(history: &History) {
+    if let Err(e) = history.save() {
+        println!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::TempPath;
+
+    // Session whose history and alias files are cleaned up with the returned guards
+    fn temp_session(name: &str) -> (Session, [TempPath; 2]) {
+        let history_path = TempPath::new(&format!("{}_history", name));
+        let alias_path = TempPath::new(&format!("{}_aliases", name));
+        let session = Session::from_files(history_path.to_path_buf(), alias_path.to_path_buf());
+        (session, [history_path, alias_path])
+    }
+
+    #[test]
+    fn dispatch_line_exits_on_exit_and_quit() {
+        let (mut session, _files) = temp_session("dispatch_exit");
+        let hal = MockHal::new();
+        assert_eq!(dispatch_line("exit", &hal, &mut session), DispatchResult::Exit);
+        assert_eq!(dispatch_line("quit", &hal, &mut session), DispatchResult::Exit);
+    }
+
+    #[test]
+    fn dispatch_line_continues_after_unknown_command() {
+        let (mut session, _files) = temp_session("dispatch_unknown");
+        assert_eq!(dispatch_line("no-such-command", &MockHal::new(), &mut session), DispatchResult::Continue);
+    }
+
+    #[test]
+    fn dispatch_line_continues_after_known_command() {
+        let (mut session, _files) = temp_session("dispatch_ver");
+        assert_eq!(dispatch_line("ver", &MockHal::new(), &mut session), DispatchResult::Continue);
+    }
+
+    #[test]
+    fn dispatch_line_exits_through_alias() {
+        let (mut session, _files) = temp_session("dispatch_alias");
+        let hal = MockHal::new();
+        assert_eq!(dispatch_line("alias q=exit", &hal, &mut session), DispatchResult::Continue);
+        assert_eq!(dispatch_line("q", &hal, &mut session), DispatchResult::Exit);
+    }
+
+    #[test]
+    fn command_names_cover_subcommands_builtins_and_aliases() {
+        let (mut session, _files) = temp_session("command_names");
+        session.aliases.define("cl=celestial_list_clouds").unwrap();
+        let names = command_names(&session.aliases);
+        for name in ["check-module-integrity", "source", "exit", "cl"] {
+            assert!(names.iter().any(|n| n == name), "missing {}", name);
         }
     }
+
+    #[test]
+    fn run_script_skips_comments_joins_continuations_and_stops_at_exit() {
+        let script = TempPath::new("script");
+        fs::write(
+            &script,
+            "# setup\n\nalias a=ping\nalias b=collapse-truth \\\n  joy calm now\n# comment ending in a backslash \\\nalias c=ver\nexit\nalias d=ver\n",
+        )
+        .unwrap();
+        let (mut session, _files) = temp_session("script");
+
+        run_script(script.to_str().unwrap(), &MockHal::new(), &mut session).unwrap();
+
+        assert_eq!(session.aliases.get("a"), Some("ping"));
+        assert_eq!(session.aliases.get("b"), Some("collapse-truth joy calm now"));
+        assert_eq!(session.aliases.get("c"), Some("ver"));
+        assert_eq!(session.aliases.get("d"), None);
+        assert_eq!(session.script_depth, 0);
+    }
+
+    #[test]
+    fn script_session_starts_without_aliases_and_keeps_its_own() {
+        let history_path = TempPath::new("script_session_history");
+        let script = TempPath::new("script_session");
+        fs::write(&script, "alias p=ping\n").unwrap();
+        let mut session = Session::for_script(history_path.to_path_buf());
+        assert_eq!(session.aliases.iter().count(), 0);
+
+        run_script(script.to_str().unwrap(), &MockHal::new(), &mut session).unwrap();
+        assert_eq!(session.aliases.get("p"), Some("ping"));
+        assert!(!history_path.exists());
+    }
+
+    #[test]
+    fn run_script_refuses_to_nest_past_max_depth() {
+        let script = TempPath::new("self_sourcing");
+        fs::write(&script, format!("source {}\n", script.display())).unwrap();
+        let (mut session, _files) = temp_session("self_sourcing");
+
+        // A script that sources itself stops at the depth limit instead of recursing forever
+        assert!(run_script(script.to_str().unwrap(), &MockHal::new(), &mut session).is_ok());
+        assert_eq!(session.script_depth, 0);
+
+        session.script_depth = MAX_SCRIPT_DEPTH;
+        assert!(run_script(script.to_str().unwrap(), &MockHal::new(), &mut session).is_err());
+    }
 }